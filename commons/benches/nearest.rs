@@ -0,0 +1,47 @@
+use commons::geometry::get_nearest_k;
+use commons::geometry::kd_tree::KdTree;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// about the size of the training set with the 5 drawing features
+const POINTS_COUNT: usize = 2415;
+const DIMENSIONS: usize = 5;
+
+fn random_points(rng: &mut StdRng, count: usize) -> Vec<Vec<f64>> {
+    (0..count)
+        .map(|_| (0..DIMENSIONS).map(|_| rng.gen_range(0.0..1.0)).collect())
+        .collect()
+}
+
+fn nearest_benchmark(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let points = random_points(&mut rng, POINTS_COUNT);
+    let queries = random_points(&mut rng, 100);
+    let tree = KdTree::create(points.clone());
+
+    for k in [1, 50] {
+        c.bench_function(&format!("linear scan k={k}"), |b| {
+            b.iter(|| {
+                for query in &queries {
+                    black_box(get_nearest_k(query, &points, k));
+                }
+            })
+        });
+
+        c.bench_function(&format!("kd-tree k={k}"), |b| {
+            b.iter(|| {
+                for query in &queries {
+                    black_box(tree.nearest_k(query, k));
+                }
+            })
+        });
+    }
+
+    c.bench_function("kd-tree build", |b| {
+        b.iter(|| black_box(KdTree::create(points.clone())))
+    });
+}
+
+criterion_group!(benches, nearest_benchmark);
+criterion_main!(benches);
@@ -1,8 +1,11 @@
+pub mod kd_tree;
+
 use crate::math::lerp::{lerp, remap};
 use crate::math::Bounds;
 use crate::utils::SomeExt;
 use binary_heap_plus::BinaryHeap as BinaryHeapExt;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::f64::consts::{PI, TAU};
 
@@ -180,6 +183,84 @@ pub fn euclidean_distance(a: &[f64], b: &[f64]) -> f64 {
         .sqrt()
 }
 
+pub fn manhattan_distance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() != b.len() {
+        panic!("incompatible points")
+    }
+
+    a.iter().zip(b).fold(0.0, |acc, (a, b)| acc + (a - b).abs())
+}
+
+pub fn chebyshev_distance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() != b.len() {
+        panic!("incompatible points")
+    }
+
+    a.iter()
+        .zip(b)
+        .fold(0.0, |acc, (a, b)| acc.max((a - b).abs()))
+}
+
+pub fn minkowski_distance(a: &[f64], b: &[f64], p: f64) -> f64 {
+    if a.len() != b.len() {
+        panic!("incompatible points")
+    }
+
+    a.iter()
+        .zip(b)
+        .fold(0.0, |acc, (a, b)| acc + (a - b).abs().powf(p))
+        .powf(1.0 / p)
+}
+
+// 1 - cosine similarity, a zero vector is treated as orthogonal to everything
+pub fn cosine_distance(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() != b.len() {
+        panic!("incompatible points")
+    }
+
+    let (dot, a_norm, b_norm) = a
+        .iter()
+        .zip(b)
+        .fold((0.0, 0.0, 0.0), |(dot, a_norm, b_norm), (a, b)| {
+            (dot + a * b, a_norm + a * a, b_norm + b * b)
+        });
+
+    let norm = (a_norm * b_norm).sqrt();
+    if norm == 0.0 {
+        return 1.0;
+    }
+
+    1.0 - dot / norm
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Metric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    Minkowski(f64),
+    Cosine,
+}
+
+impl Metric {
+    pub fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        match self {
+            Metric::Euclidean => euclidean_distance(a, b),
+            Metric::Manhattan => manhattan_distance(a, b),
+            Metric::Chebyshev => chebyshev_distance(a, b),
+            Metric::Minkowski(p) => minkowski_distance(a, b, *p),
+            Metric::Cosine => cosine_distance(a, b),
+        }
+    }
+
+    // whether a difference along one axis never exceeds the distance,
+    // which is what allows to skip branches of a KdTree
+    pub fn is_axis_bounded(&self) -> bool {
+        !matches!(self, Metric::Cosine)
+    }
+}
+
 pub type PointN = Vec<f64>;
 pub type PolygonN = Vec<PointN>;
 
@@ -268,16 +349,16 @@ pub fn polygon_area(polygon: &Vec<[f64; 2]>) -> f64 {
 
 pub fn polygon_roundness(polygon: &Vec<[f64; 2]>) -> f64 {
     let length = polygon_length(polygon);
+    // a point has no circle to compare with
+    if length == 0.0 {
+        return 0.0;
+    }
+
     let area = polygon_area(polygon);
     let radius = length / TAU;
     let cycle_area = PI * radius.powi(2);
-    let result = area / cycle_area;
 
-    if result.is_nan() {
-        return 0.0;
-    }
-
-    result
+    area / cycle_area
 }
 
 // finds a point with the lowest vertical position (leftmost wins in case of a tie)
@@ -474,14 +555,14 @@ pub fn minimum_bounding_box(hull: &Vec<[f64; 2]>) -> Option<(Vec<[f64; 2]>, f64,
 #[cfg(test)]
 mod tests {
     use crate::geometry::{
-        coincident_box, euclidean_distance, get_nearest, polygon_area, polygon_length,
+        coincident_box, euclidean_distance, get_nearest, polygon_area, polygon_length, Metric,
     };
 
     #[test]
     fn test_get_nearest() {
         let point1 = vec![1.0, 3.0];
 
-        assert_eq!(get_nearest(&point1, &[]), vec![]);
+        assert_eq!(get_nearest(&point1, &[]), Vec::<usize>::new());
     }
 
     #[test]
@@ -492,6 +573,18 @@ mod tests {
         assert_eq!(euclidean_distance(&point1, &point2), 5.0);
     }
 
+    #[test]
+    fn test_metrics() {
+        let point1 = [1.0, 3.0];
+        let point2 = [5.0, 0.0];
+
+        assert_eq!(Metric::Manhattan.distance(&point1, &point2), 7.0);
+        assert_eq!(Metric::Chebyshev.distance(&point1, &point2), 4.0);
+        assert!((Metric::Minkowski(2.0).distance(&point1, &point2) - 5.0).abs() < 1e-12);
+        assert!((Metric::Cosine.distance(&[1.0, 0.0], &[0.0, 2.0]) - 1.0).abs() < 1e-12);
+        assert!(Metric::Cosine.distance(&[1.0, 1.0], &[2.0, 2.0]).abs() < 1e-12);
+    }
+
     #[test]
     fn test_cycle_iter() {
         let points = vec![1, 2, 3];
@@ -0,0 +1,327 @@
+use crate::geometry::{Metric, PointN};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+// k-dimensional tree over PointN, the points are split by the median of one axis per level
+// https://en.wikipedia.org/wiki/K-d_tree
+#[derive(Clone, Default)]
+pub struct KdTree {
+    points: Vec<PointN>,
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+#[derive(Clone)]
+struct Node {
+    // index of the point in the input
+    index: usize,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// ordered by the distance, ties are resolved by the lower index
+#[derive(PartialEq)]
+struct Neighbour {
+    distance: f64,
+    index: usize,
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+impl KdTree {
+    pub fn create(points: Vec<PointN>) -> Self {
+        let dimensions = points.first().map(|x| x.len()).unwrap_or(0).max(1);
+        let mut indices = (0..points.len()).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(points.len());
+
+        let root = Self::build(&points, &mut nodes, &mut indices, 0, dimensions);
+
+        Self {
+            points,
+            nodes,
+            root,
+        }
+    }
+
+    fn build(
+        points: &[PointN],
+        nodes: &mut Vec<Node>,
+        indices: &mut [usize],
+        depth: usize,
+        dimensions: usize,
+    ) -> Option<usize> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let axis = depth % dimensions;
+        let median = indices.len() / 2;
+        indices.select_nth_unstable_by(median, |a, b| {
+            coordinate(&points[*a], axis).total_cmp(&coordinate(&points[*b], axis))
+        });
+
+        let index = indices[median];
+        let (left, right) = indices.split_at_mut(median);
+        let left = Self::build(points, nodes, left, depth + 1, dimensions);
+        let right = Self::build(points, nodes, &mut right[1..], depth + 1, dimensions);
+
+        nodes.push(Node {
+            index,
+            axis,
+            left,
+            right,
+        });
+
+        Some(nodes.len() - 1)
+    }
+
+    pub fn points(&self) -> &[PointN] {
+        &self.points
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn nearest(&self, point: &[f64]) -> Option<usize> {
+        self.nearest_k(point, 1).first().copied()
+    }
+
+    // indices of the k nearest points, the nearest first
+    pub fn nearest_k(&self, point: &[f64], k: usize) -> Vec<usize> {
+        self.nearest_k_with_distances(point, k)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // indices of the k nearest points with their euclidean distances, the nearest first
+    pub fn nearest_k_with_distances(&self, point: &[f64], k: usize) -> Vec<(usize, f64)> {
+        self.nearest_k_by(point, k, Metric::Euclidean)
+    }
+
+    // same as nearest_k_with_distances, but with the given metric,
+    // metrics without axis bounds (cosine) fall back to a linear scan
+    pub fn nearest_k_by(&self, point: &[f64], k: usize, metric: Metric) -> Vec<(usize, f64)> {
+        if k == 0 {
+            return vec![];
+        }
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if metric.is_axis_bounded() {
+            self.search_k(self.root, point, k, metric, &mut heap);
+        } else {
+            self.points.iter().zip(0..).for_each(|(x, index)| {
+                let distance = metric.distance(point, x);
+                push_candidate(&mut heap, Neighbour { distance, index }, k)
+            });
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|x| (x.index, x.distance))
+            .collect()
+    }
+
+    // the heap keeps the k best candidates found so far with the farthest on top
+    fn search_k(
+        &self,
+        node: Option<usize>,
+        point: &[f64],
+        k: usize,
+        metric: Metric,
+        heap: &mut BinaryHeap<Neighbour>,
+    ) {
+        let node = match node {
+            Some(node) => &self.nodes[node],
+            None => return,
+        };
+
+        let candidate = Neighbour {
+            distance: metric.distance(point, &self.points[node.index]),
+            index: node.index,
+        };
+        push_candidate(heap, candidate, k);
+
+        let diff = coordinate(point, node.axis) - coordinate(&self.points[node.index], node.axis);
+        let (near, far) = if diff < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+
+        self.search_k(near, point, k, metric, heap);
+
+        // the other side may only contain closer points if the splitting plane is close enough
+        let worst = heap.peek().map(|x| x.distance).unwrap_or(f64::INFINITY);
+        if heap.len() < k || diff.abs() <= worst {
+            self.search_k(far, point, k, metric, heap);
+        }
+    }
+
+    // indices of all points within the euclidean radius (inclusive), the nearest first
+    pub fn within_radius(&self, point: &[f64], radius: f64) -> Vec<usize> {
+        self.within_radius_by(point, radius, Metric::Euclidean)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn within_radius_by(
+        &self,
+        point: &[f64],
+        radius: f64,
+        metric: Metric,
+    ) -> Vec<(usize, f64)> {
+        let mut result = vec![];
+        if metric.is_axis_bounded() {
+            self.search_radius(self.root, point, radius, metric, &mut result);
+        } else {
+            self.points.iter().zip(0..).for_each(|(x, index)| {
+                let distance = metric.distance(point, x);
+                if distance <= radius {
+                    result.push(Neighbour { distance, index })
+                }
+            });
+        }
+
+        result.sort();
+        result.into_iter().map(|x| (x.index, x.distance)).collect()
+    }
+
+    fn search_radius(
+        &self,
+        node: Option<usize>,
+        point: &[f64],
+        radius: f64,
+        metric: Metric,
+        result: &mut Vec<Neighbour>,
+    ) {
+        let node = match node {
+            Some(node) => &self.nodes[node],
+            None => return,
+        };
+
+        let distance = metric.distance(point, &self.points[node.index]);
+        if distance <= radius {
+            result.push(Neighbour {
+                distance,
+                index: node.index,
+            });
+        }
+
+        let diff = coordinate(point, node.axis) - coordinate(&self.points[node.index], node.axis);
+        if diff <= 0.0 || diff.abs() <= radius {
+            self.search_radius(node.left, point, radius, metric, result);
+        }
+        if diff >= 0.0 || diff.abs() <= radius {
+            self.search_radius(node.right, point, radius, metric, result);
+        }
+    }
+}
+
+fn push_candidate(heap: &mut BinaryHeap<Neighbour>, candidate: Neighbour, k: usize) {
+    if heap.len() < k {
+        heap.push(candidate);
+    } else if heap.peek().map(|x| candidate < *x).unwrap_or(false) {
+        heap.pop();
+        heap.push(candidate);
+    }
+}
+
+fn coordinate(point: &[f64], axis: usize) -> f64 {
+    point.get(axis).copied().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::kd_tree::KdTree;
+    use crate::geometry::Metric;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn brute_force_k(points: &[Vec<f64>], point: &[f64], k: usize, metric: Metric) -> Vec<usize> {
+        let mut indices = (0..points.len()).collect::<Vec<_>>();
+        indices.sort_by(|a, b| {
+            metric
+                .distance(point, &points[*a])
+                .total_cmp(&metric.distance(point, &points[*b]))
+                .then(a.cmp(b))
+        });
+        indices.truncate(k);
+        indices
+    }
+
+    #[test]
+    fn test_nearest_k_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let points = (0..500)
+            .map(|_| (0..5).map(|_| rng.gen_range(0.0..1.0)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let tree = KdTree::create(points.clone());
+
+        let metrics = [
+            Metric::Euclidean,
+            Metric::Manhattan,
+            Metric::Chebyshev,
+            Metric::Minkowski(3.0),
+            Metric::Cosine,
+        ];
+
+        for _ in 0..50 {
+            let point = (0..5).map(|_| rng.gen_range(0.0..1.0)).collect::<Vec<_>>();
+
+            for metric in metrics {
+                let nearest = tree
+                    .nearest_k_by(&point, 20, metric)
+                    .into_iter()
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+
+                assert_eq!(nearest, brute_force_k(&points, &point, 20, metric));
+            }
+        }
+    }
+
+    #[test]
+    fn test_within_radius() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 2.0],
+            vec![3.0, 3.0],
+        ];
+        let tree = KdTree::create(points);
+
+        assert_eq!(tree.within_radius(&[0.1, 0.0], 1.0), vec![0, 1]);
+        assert_eq!(tree.within_radius(&[0.0, 0.0], 2.0), vec![0, 1, 2]);
+        assert_eq!(tree.within_radius(&[10.0, 10.0], 1.0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = KdTree::create(vec![]);
+
+        assert_eq!(tree.nearest(&[1.0, 2.0]), None);
+        assert!(tree.within_radius(&[1.0, 2.0], 5.0).is_empty());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod lerp;
+pub mod normalization;
+pub mod pca;
+pub mod tsne;
 
 use crate::geometry::Point2D;
-use crate::math::lerp::inv_lerp;
+use crate::math::lerp::lerp;
+use serde::{Deserialize, Serialize};
 
 pub trait PointExt {
     fn distance(&self, to: &Self) -> f64;
@@ -35,7 +39,7 @@ impl std::ops::Add<Point2D> for Point2D {
     }
 }
 
-#[derive(Default, Clone, Debug, PartialEq)]
+#[derive(Default, Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct Bounds {
     pub left: f64,
     pub right: f64,
@@ -117,33 +121,46 @@ pub fn min_max_n_points(points: &Vec<Vec<f64>>) -> Option<(Vec<f64>, Vec<f64>)>
     Some((min, max))
 }
 
-pub fn normalize_points_to_min_max(points: Vec<Vec<f64>>) -> ((Vec<f64>, Vec<f64>), Vec<Vec<f64>>) {
-    match min_max_n_points(&points) {
-        Some((min, max)) => {
-            let points = normalize_points(&min, &max, points);
-            ((min, max), points)
-        }
-        None => {
-            panic!("empty input")
-        }
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// population standard deviation
+pub fn standard_deviation(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
     }
+
+    let mean = mean(values);
+    let variance = values.iter().fold(0.0, |acc, x| acc + (x - mean).powi(2)) / values.len() as f64;
+
+    variance.sqrt()
 }
 
-pub fn normalize_points(min: &Vec<f64>, max: &Vec<f64>, points: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
-    points
-        .into_iter()
-        .map(|row| {
-            row.into_iter()
-                .zip(0..)
-                .map(|(v, i)| inv_lerp(min[i], max[i], v))
-                .collect()
-        })
-        .collect()
+// linearly interpolated between the closest ranks
+pub fn quantile(values: &[f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+
+    lerp(sorted[lower], sorted[upper], position - lower as f64)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::geometry::get_nearest;
+    use crate::math::{mean, quantile, standard_deviation};
     use binary_heap_plus::BinaryHeap as BinaryHeapExt;
 
     #[test]
@@ -171,4 +188,23 @@ mod tests {
         heap.push(3);
         assert_eq!(&1, heap.peek().expect(""))
     }
+
+    #[test]
+    fn test_mean_and_standard_deviation() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+        assert_eq!(mean(&values), 5.0);
+        assert_eq!(standard_deviation(&values), 2.0);
+        assert_eq!(standard_deviation(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_quantile() {
+        let values = [7.0, 1.0, 3.0, 5.0];
+
+        assert_eq!(quantile(&values, 0.5), 4.0);
+        assert_eq!(quantile(&values, 0.25), 2.5);
+        assert_eq!(quantile(&values, 1.0), 7.0);
+        assert_eq!(quantile(&[], 0.5), 0.0);
+    }
 }
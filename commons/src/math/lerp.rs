@@ -2,9 +2,10 @@ pub fn lerp(a: f64, b: f64, t: f64) -> f64 {
     a * (1.0 - t) + b * t
 }
 
+// an empty range maps every value to its start
 pub fn inv_lerp(a: f64, b: f64, v: f64) -> f64 {
-    if b == a && v == a {
-        return v;
+    if b == a {
+        return 0.0;
     }
 
     (v - a) / (b - a)
@@ -14,3 +15,16 @@ pub fn remap(from_a: f64, from_b: f64, to_a: f64, to_b: f64, v: f64) -> f64 {
     let t = inv_lerp(from_a, from_b, v);
     lerp(to_a, to_b, t)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::math::lerp::{inv_lerp, remap};
+
+    #[test]
+    fn test_inv_lerp() {
+        assert_eq!(inv_lerp(2.0, 4.0, 3.0), 0.5);
+        assert_eq!(inv_lerp(2.0, 2.0, 2.0), 0.0);
+        assert_eq!(inv_lerp(2.0, 2.0, 5.0), 0.0);
+        assert_eq!(remap(1.0, 1.0, 10.0, 20.0, 3.0), 10.0);
+    }
+}
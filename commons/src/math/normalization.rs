@@ -0,0 +1,202 @@
+use crate::math::{mean, min_max_n_points, quantile, standard_deviation};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NormalizationKind {
+    #[default]
+    MinMax,
+    ZScore,
+    // median and interquartile range, outliers don't squash the other values
+    Robust,
+}
+
+// per column transform fitted on the training points, stored with its kind,
+// so the same transform can be applied to new points
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Normalizer {
+    MinMax { min: Vec<f64>, max: Vec<f64> },
+    ZScore { mean: Vec<f64>, std: Vec<f64> },
+    Robust { median: Vec<f64>, iqr: Vec<f64> },
+}
+
+fn columns(points: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let dimensions = points.first().map(|x| x.len()).unwrap_or(0);
+
+    (0..dimensions)
+        .map(|index| points.iter().map(|x| x[index]).collect())
+        .collect()
+}
+
+// a zero spread (a constant column) only centers the column instead of dividing by zero
+fn scale(value: f64, center: f64, spread: f64) -> f64 {
+    if spread == 0.0 {
+        return value - center;
+    }
+
+    (value - center) / spread
+}
+
+fn unscale(value: f64, center: f64, spread: f64) -> f64 {
+    if spread == 0.0 {
+        return value + center;
+    }
+
+    value * spread + center
+}
+
+impl Normalizer {
+    pub fn fit(kind: NormalizationKind, points: &[Vec<f64>]) -> Self {
+        match kind {
+            NormalizationKind::MinMax => {
+                let (min, max) = min_max_n_points(&points.to_vec()).unwrap_or_default();
+                Normalizer::MinMax { min, max }
+            }
+            NormalizationKind::ZScore => {
+                let columns = columns(points);
+                Normalizer::ZScore {
+                    mean: columns.iter().map(|x| mean(x)).collect(),
+                    std: columns.iter().map(|x| standard_deviation(x)).collect(),
+                }
+            }
+            NormalizationKind::Robust => {
+                let columns = columns(points);
+                Normalizer::Robust {
+                    median: columns.iter().map(|x| quantile(x, 0.5)).collect(),
+                    iqr: columns
+                        .iter()
+                        .map(|x| quantile(x, 0.75) - quantile(x, 0.25))
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    pub fn kind(&self) -> NormalizationKind {
+        match self {
+            Normalizer::MinMax { .. } => NormalizationKind::MinMax,
+            Normalizer::ZScore { .. } => NormalizationKind::ZScore,
+            Normalizer::Robust { .. } => NormalizationKind::Robust,
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Normalizer::MinMax { min, .. } => min.len(),
+            Normalizer::ZScore { mean, .. } => mean.len(),
+            Normalizer::Robust { median, .. } => median.len(),
+        }
+    }
+
+    pub fn transform(&self, point: &[f64]) -> Vec<f64> {
+        point
+            .iter()
+            .zip(0..)
+            .map(|(value, i)| match self {
+                Normalizer::MinMax { min, max } => scale(*value, min[i], max[i] - min[i]),
+                Normalizer::ZScore { mean, std } => scale(*value, mean[i], std[i]),
+                Normalizer::Robust { median, iqr } => scale(*value, median[i], iqr[i]),
+            })
+            .collect()
+    }
+
+    pub fn transform_points(&self, points: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+        points.iter().map(|x| self.transform(x)).collect()
+    }
+
+    // the point before `transform`, up to the rounding of the floats
+    pub fn inverse_transform(&self, point: &[f64]) -> Vec<f64> {
+        point
+            .iter()
+            .zip(0..)
+            .map(|(value, i)| match self {
+                Normalizer::MinMax { min, max } => unscale(*value, min[i], max[i] - min[i]),
+                Normalizer::ZScore { mean, std } => unscale(*value, mean[i], std[i]),
+                Normalizer::Robust { median, iqr } => unscale(*value, median[i], iqr[i]),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::normalization::{NormalizationKind, Normalizer};
+
+    fn points() -> Vec<Vec<f64>> {
+        vec![
+            vec![1.0, 5.0],
+            vec![2.0, 5.0],
+            vec![3.0, 5.0],
+            vec![4.0, 5.0],
+            vec![100.0, 5.0],
+        ]
+    }
+
+    #[test]
+    fn test_min_max() {
+        let normalizer = Normalizer::fit(NormalizationKind::MinMax, &points());
+        assert_eq!(
+            normalizer,
+            Normalizer::MinMax {
+                min: vec![1.0, 5.0],
+                max: vec![100.0, 5.0]
+            }
+        );
+        assert_eq!(normalizer.transform(&[100.0, 5.0]), vec![1.0, 0.0]);
+        assert_eq!(normalizer.transform(&[50.5, 7.0]), vec![0.5, 2.0]);
+    }
+
+    #[test]
+    fn test_z_score() {
+        let normalizer = Normalizer::fit(NormalizationKind::ZScore, &[vec![1.0], vec![3.0]]);
+        assert_eq!(
+            normalizer.transform_points(vec![vec![1.0], vec![2.0], vec![5.0]]),
+            vec![vec![-1.0], vec![0.0], vec![3.0]]
+        );
+    }
+
+    #[test]
+    fn test_robust() {
+        let normalizer = Normalizer::fit(NormalizationKind::Robust, &points());
+        assert_eq!(
+            normalizer,
+            Normalizer::Robust {
+                median: vec![3.0, 5.0],
+                iqr: vec![2.0, 0.0]
+            }
+        );
+
+        // the outlier doesn't change the scale of the other values
+        assert_eq!(normalizer.transform(&[4.0, 6.0]), vec![0.5, 1.0]);
+        assert_eq!(normalizer.kind(), NormalizationKind::Robust);
+    }
+
+    #[test]
+    fn test_inverse_transform() {
+        for kind in [
+            NormalizationKind::MinMax,
+            NormalizationKind::ZScore,
+            NormalizationKind::Robust,
+        ] {
+            let normalizer = Normalizer::fit(kind, &points());
+            let point = [50.5, 7.0];
+            let result = normalizer.inverse_transform(&normalizer.transform(&point));
+            assert_eq!(result, point);
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let normalizer = Normalizer::ZScore {
+            mean: vec![1.0],
+            std: vec![0.5],
+        };
+
+        let json = serde_json::to_string(&normalizer).expect("");
+        assert_eq!(json, r#"{"kind":"zScore","mean":[1.0],"std":[0.5]}"#);
+        assert_eq!(
+            serde_json::from_str::<Normalizer>(&json).expect(""),
+            normalizer
+        );
+    }
+}
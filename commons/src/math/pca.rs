@@ -0,0 +1,293 @@
+use crate::geometry::PointN;
+use serde::{Deserialize, Serialize};
+
+// jacobi rotations stop once the off-diagonal part is this small relative to the diagonal
+const EPSILON: f64 = 1e-24;
+const MAX_SWEEPS: usize = 100;
+
+// principal component analysis, the components are the eigenvectors
+// of the covariance matrix sorted by their eigenvalues (the explained variance)
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pca {
+    mean: PointN,
+    components: Vec<PointN>,
+    explained_variance: Vec<f64>,
+    total_variance: f64,
+}
+
+// sample covariance of the centered points
+fn covariance(points: &[PointN], mean: &[f64]) -> Vec<Vec<f64>> {
+    let dimensions = mean.len();
+    let mut result = vec![vec![0.0; dimensions]; dimensions];
+
+    for point in points {
+        let centered = point
+            .iter()
+            .zip(mean)
+            .map(|(x, m)| x - m)
+            .collect::<Vec<_>>();
+        for (row, a) in result.iter_mut().zip(&centered) {
+            for (value, b) in row.iter_mut().zip(&centered) {
+                *value += a * b;
+            }
+        }
+    }
+
+    let count = points.len().saturating_sub(1).max(1) as f64;
+    result.iter_mut().flatten().for_each(|x| *x /= count);
+
+    result
+}
+
+// eigenvalues and eigenvectors (as columns) of a symmetric matrix, cyclic jacobi method
+fn symmetric_eigen(mut matrix: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let size = matrix.len();
+    let mut vectors = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect::<Vec<Vec<f64>>>();
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal = (0..size)
+            .flat_map(|i| (i + 1..size).map(move |j| (i, j)))
+            .map(|(i, j)| matrix[i][j].powi(2))
+            .sum::<f64>();
+        let diagonal = (0..size).map(|i| matrix[i][i].powi(2)).sum::<f64>();
+        if off_diagonal <= EPSILON * diagonal {
+            break;
+        }
+
+        for p in 0..size {
+            for q in p + 1..size {
+                if matrix[p][q].abs() < f64::MIN_POSITIVE {
+                    continue;
+                }
+
+                // the rotation which zeroes matrix[p][q]
+                let theta = (matrix[q][q] - matrix[p][p]) / (2.0 * matrix[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                // columns p and q of the matrix and of the eigenvectors, then its rows p and q
+                for row in matrix.iter_mut().chain(vectors.iter_mut()) {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+
+                let (top, bottom) = matrix.split_at_mut(q);
+                for (pk, qk) in top[p].iter_mut().zip(bottom[0].iter_mut()) {
+                    let (a, b) = (*pk, *qk);
+                    *pk = c * a - s * b;
+                    *qk = s * a + c * b;
+                }
+            }
+        }
+    }
+
+    ((0..size).map(|i| matrix[i][i]).collect(), vectors)
+}
+
+impl Pca {
+    // keeps `components_count` components at most, the points must have the same dimensions
+    pub fn fit(points: &[PointN], components_count: usize) -> Self {
+        let dimensions = points.first().map(|x| x.len()).unwrap_or(0);
+
+        let mut mean = vec![0.0; dimensions];
+        for point in points {
+            mean.iter_mut().zip(point).for_each(|(m, x)| *m += x);
+        }
+        mean.iter_mut()
+            .for_each(|x| *x /= points.len().max(1) as f64);
+
+        let (values, vectors) = symmetric_eigen(covariance(points, &mean));
+        let total_variance = values.iter().map(|x| x.max(0.0)).sum();
+
+        let mut order = (0..dimensions).collect::<Vec<_>>();
+        order.sort_by(|a, b| values[*b].total_cmp(&values[*a]));
+
+        let (explained_variance, components) = order
+            .into_iter()
+            .take(components_count)
+            .map(|index| {
+                let mut component = vectors.iter().map(|row| row[index]).collect::<Vec<_>>();
+
+                // the sign is arbitrary, the largest coordinate is made positive to be stable
+                let largest = component
+                    .iter()
+                    .copied()
+                    .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+                    .unwrap_or_default();
+                if largest < 0.0 {
+                    component.iter_mut().for_each(|x| *x = -*x);
+                }
+
+                (values[index].max(0.0), component)
+            })
+            .unzip();
+
+        Self {
+            mean,
+            components,
+            explained_variance,
+            total_variance,
+        }
+    }
+
+    pub fn components(&self) -> &[PointN] {
+        &self.components
+    }
+
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    pub fn explained_variance(&self) -> &[f64] {
+        &self.explained_variance
+    }
+
+    // the share of the whole variance kept by every component
+    pub fn explained_variance_ratio(&self) -> Vec<f64> {
+        self.explained_variance
+            .iter()
+            .map(|x| {
+                if self.total_variance > 0.0 {
+                    x / self.total_variance
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    pub fn transform(&self, point: &[f64]) -> PointN {
+        self.components
+            .iter()
+            .map(|component| {
+                component
+                    .iter()
+                    .zip(point.iter().zip(&self.mean))
+                    .map(|(c, (x, m))| c * (x - m))
+                    .sum()
+            })
+            .collect()
+    }
+
+    pub fn transform_points(&self, points: &[PointN]) -> Vec<PointN> {
+        points.iter().map(|x| self.transform(x)).collect()
+    }
+
+    // the point of the original space, it loses the dropped components
+    pub fn inverse_transform(&self, projected: &[f64]) -> PointN {
+        let mut result = self.mean.clone();
+
+        for (component, value) in self.components.iter().zip(projected) {
+            result
+                .iter_mut()
+                .zip(component)
+                .for_each(|(x, c)| *x += c * value);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::pca::{covariance, Pca};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn assert_close(result: &[f64], expected: &[f64]) {
+        assert_eq!(result.len(), expected.len());
+        result
+            .iter()
+            .zip(expected)
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-9, "{result:?} != {expected:?}"));
+    }
+
+    #[test]
+    fn test_line() {
+        // every point lies on the y = 2x line
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![1.0, 2.0],
+            vec![2.0, 4.0],
+            vec![3.0, 6.0],
+        ];
+        let pca = Pca::fit(&points, 2);
+
+        let norm = 5.0_f64.sqrt();
+        assert_close(&pca.components()[0], &[1.0 / norm, 2.0 / norm]);
+        assert_close(&pca.explained_variance_ratio(), &[1.0, 0.0]);
+        assert_close(pca.mean(), &[1.5, 3.0]);
+
+        let projected = pca.transform(&[3.0, 6.0]);
+        assert_close(&projected, &[1.5 * norm, 0.0]);
+        assert_close(&pca.inverse_transform(&projected), &[3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_reduction() {
+        let points = vec![
+            vec![2.5, 2.4, 1.0],
+            vec![0.5, 0.7, 1.0],
+            vec![2.2, 2.9, 1.0],
+            vec![1.9, 2.2, 1.0],
+            vec![3.1, 3.0, 1.0],
+            vec![2.3, 2.7, 1.0],
+            vec![2.0, 1.6, 1.0],
+            vec![1.0, 1.1, 1.0],
+            vec![1.5, 1.6, 1.0],
+            vec![1.1, 0.9, 1.0],
+        ];
+        let pca = Pca::fit(&points, 2);
+
+        // the known result of this data set, the constant third axis has no variance
+        assert_close(
+            pca.explained_variance(),
+            &[1.2840277121727839, 0.04908339893832736],
+        );
+        let ratio = pca.explained_variance_ratio();
+        assert!((ratio.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        let projected = pca.transform_points(&points);
+        assert_eq!(projected[0].len(), 2);
+        for (point, projected) in points.iter().zip(&projected) {
+            assert_close(&pca.inverse_transform(projected), point);
+        }
+    }
+
+    #[test]
+    fn test_orthonormal_components() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let points = (0..200)
+            .map(|_| {
+                let base = rng.gen_range(-1.0..1.0);
+                (0..12)
+                    .map(|i| base * i as f64 + rng.gen_range(-1.0..1.0) * 100.0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let pca = Pca::fit(&points, 12);
+
+        for (i, a) in pca.components().iter().enumerate() {
+            for (j, b) in pca.components().iter().enumerate() {
+                let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-9);
+            }
+        }
+
+        let mean = pca.mean().to_vec();
+        let trace = covariance(&points, &mean)
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[i])
+            .sum::<f64>();
+        let variance = pca.explained_variance();
+        assert!((variance.iter().sum::<f64>() - trace).abs() < 1e-6 * trace);
+        assert!(variance.windows(2).all(|x| x[0] >= x[1]));
+    }
+}
@@ -0,0 +1,295 @@
+use crate::geometry::PointN;
+use crate::math::pca::Pca;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::TAU;
+
+// wider inputs are reduced with PCA first, distances in them are mostly noise
+const PCA_DIMENSIONS: usize = 50;
+// every point is attracted by its 3 * perplexity nearest neighbours only
+const NEIGHBOURS_PER_PERPLEXITY: f64 = 3.0;
+const EARLY_EXAGGERATION: f64 = 12.0;
+const EXAGGERATION_ITERATIONS: usize = 250;
+const MIN_GAIN: f64 = 0.01;
+
+// t-distributed stochastic neighbour embedding, the same seed gives the same embedding
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tsne {
+    pub dimensions: usize,
+    // about the number of the effective neighbours of every point
+    pub perplexity: f64,
+    pub iterations: usize,
+    // scaled by the number of the points when none
+    pub learning_rate: Option<f64>,
+    pub seed: u64,
+}
+
+impl Default for Tsne {
+    fn default() -> Self {
+        Self {
+            dimensions: 2,
+            perplexity: 30.0,
+            iterations: 1000,
+            learning_rate: None,
+            seed: 42,
+        }
+    }
+}
+
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+// conditional probabilities of the neighbours, the gaussian width is
+// binary searched so the entropy matches the perplexity
+fn neighbour_probabilities(distances: &[(usize, f64)], perplexity: f64) -> Vec<f64> {
+    let target = perplexity.ln();
+    let (mut beta, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+    let mut result = vec![0.0; distances.len()];
+
+    let min_distance = distances.iter().map(|x| x.1).fold(f64::INFINITY, f64::min);
+
+    for _ in 0..100 {
+        // shifted by the closest distance, so the exponents don't underflow
+        result
+            .iter_mut()
+            .zip(distances)
+            .for_each(|(p, (_, d))| *p = (-(d - min_distance) * beta).exp());
+        let sum = result.iter().sum::<f64>();
+
+        let entropy = sum.ln()
+            + beta
+                * result
+                    .iter()
+                    .zip(distances)
+                    .map(|(p, (_, d))| p * (d - min_distance))
+                    .sum::<f64>()
+                / sum;
+        result.iter_mut().for_each(|p| *p /= sum);
+
+        if (entropy - target).abs() < 1e-5 {
+            break;
+        }
+        if entropy > target {
+            low = beta;
+            beta = if high.is_infinite() {
+                beta * 2.0
+            } else {
+                (beta + high) / 2.0
+            };
+        } else {
+            high = beta;
+            beta = (beta + low) / 2.0;
+        }
+    }
+
+    result
+}
+
+// symmetric joint probabilities of the neighbour pairs, every pair is stored once (i < j),
+// so they add up to a half
+fn joint_probabilities(points: &[PointN], perplexity: f64) -> Vec<(usize, usize, f64)> {
+    let count = points.len();
+    let neighbours = ((NEIGHBOURS_PER_PERPLEXITY * perplexity) as usize).clamp(1, count - 1);
+    let perplexity = perplexity.min(neighbours as f64);
+
+    let mut pairs = vec![];
+    for (i, point) in points.iter().enumerate() {
+        let mut distances = points
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(j, other)| (j, squared_distance(point, other)))
+            .collect::<Vec<_>>();
+        distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+        distances.truncate(neighbours);
+
+        let probabilities = neighbour_probabilities(&distances, perplexity);
+        for ((j, _), p) in distances.into_iter().zip(probabilities) {
+            pairs.push((i.min(j), i.max(j), p));
+        }
+    }
+
+    // p(i|j) and p(j|i) of the same pair are added up
+    pairs.sort_by_key(|x| (x.0, x.1));
+    let mut result: Vec<(usize, usize, f64)> = vec![];
+    for (i, j, p) in pairs {
+        match result.last_mut() {
+            Some(last) if last.0 == i && last.1 == j => last.2 += p,
+            _ => result.push((i, j, p)),
+        }
+    }
+
+    let total = 2.0 * result.iter().map(|x| x.2).sum::<f64>();
+    result.iter_mut().for_each(|x| x.2 /= total);
+    result
+}
+
+// standard normal values by the Box-Muller transform
+fn gaussian(rng: &mut impl Rng) -> f64 {
+    let u = 1.0 - rng.gen::<f64>();
+    let v = rng.gen::<f64>();
+    (-2.0 * u.ln()).sqrt() * (TAU * v).cos()
+}
+
+impl Tsne {
+    pub fn embed(&self, points: &[PointN]) -> Vec<PointN> {
+        let count = points.len();
+        let dimensions = self.dimensions;
+        if count < 2 {
+            return vec![vec![0.0; dimensions]; count];
+        }
+
+        let input_dimensions = points[0].len();
+        let reduced;
+        let points = if input_dimensions > PCA_DIMENSIONS {
+            reduced = Pca::fit(points, PCA_DIMENSIONS).transform_points(points);
+            &reduced
+        } else {
+            points
+        };
+
+        let pairs = joint_probabilities(points, self.perplexity);
+        let learning_rate = self
+            .learning_rate
+            .unwrap_or((count as f64 / EARLY_EXAGGERATION / 4.0).max(50.0));
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut result = (0..count)
+            .map(|_| (0..dimensions).map(|_| gaussian(&mut rng) * 1e-4).collect())
+            .collect::<Vec<PointN>>();
+        let mut updates = vec![vec![0.0_f64; dimensions]; count];
+        let mut gains = vec![vec![1.0_f64; dimensions]; count];
+
+        for iteration in 0..self.iterations {
+            let (exaggeration, momentum) = if iteration < EXAGGERATION_ITERATIONS {
+                (EARLY_EXAGGERATION, 0.5)
+            } else {
+                (1.0, 0.8)
+            };
+
+            let gradients = self.gradients(&result, &pairs, exaggeration);
+
+            let values = result.iter_mut().flatten();
+            let steps = updates.iter_mut().flatten().zip(gains.iter_mut().flatten());
+            for ((value, gradient), (update, gain)) in
+                values.zip(gradients.iter().flatten()).zip(steps)
+            {
+                // the step grows while the direction is kept
+                *gain = if gradient.signum() != update.signum() {
+                    *gain + 0.2
+                } else {
+                    (*gain * 0.8).max(MIN_GAIN)
+                };
+                *update = momentum * *update - learning_rate * *gain * gradient;
+                *value += *update;
+            }
+
+            // the embedding is kept centered
+            for d in 0..dimensions {
+                let mean = result.iter().map(|x| x[d]).sum::<f64>() / count as f64;
+                result.iter_mut().for_each(|x| x[d] -= mean);
+            }
+        }
+
+        result
+    }
+
+    // attraction of the neighbour pairs minus the repulsion of all pairs
+    fn gradients(
+        &self,
+        points: &[PointN],
+        pairs: &[(usize, usize, f64)],
+        exaggeration: f64,
+    ) -> Vec<PointN> {
+        let dimensions = self.dimensions;
+        let mut repulsion = vec![vec![0.0; dimensions]; points.len()];
+        let mut normalization = 0.0;
+
+        for (i, a) in points.iter().enumerate() {
+            for (j, b) in points.iter().enumerate().skip(i + 1) {
+                let kernel = 1.0 / (1.0 + squared_distance(a, b));
+                normalization += 2.0 * kernel;
+
+                let force = kernel * kernel;
+                for d in 0..dimensions {
+                    let value = force * (a[d] - b[d]);
+                    repulsion[i][d] += value;
+                    repulsion[j][d] -= value;
+                }
+            }
+        }
+
+        let mut result = repulsion
+            .into_iter()
+            .map(|x| x.into_iter().map(|x| -4.0 * x / normalization).collect())
+            .collect::<Vec<PointN>>();
+
+        for (i, j, p) in pairs {
+            let (a, b) = (&points[*i], &points[*j]);
+            let force = 4.0 * exaggeration * p / (1.0 + squared_distance(a, b));
+
+            for d in 0..dimensions {
+                let value = force * (a[d] - b[d]);
+                result[*i][d] += value;
+                result[*j][d] -= value;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::tsne::{joint_probabilities, Tsne};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // three well separated clusters in 5 dimensions
+    fn clusters() -> Vec<Vec<f64>> {
+        let mut rng = StdRng::seed_from_u64(1);
+        (0..60)
+            .map(|index| {
+                let center = (index % 3) as f64 * 10.0;
+                (0..5).map(|_| center + rng.gen_range(-1.0..1.0)).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_joint_probabilities() {
+        let pairs = joint_probabilities(&clusters(), 5.0);
+
+        assert!((pairs.iter().map(|x| x.2).sum::<f64>() - 0.5).abs() < 1e-9);
+        assert!(pairs.iter().all(|x| x.0 < x.1));
+    }
+
+    #[test]
+    fn test_embed() {
+        let points = clusters();
+        let tsne = Tsne {
+            perplexity: 10.0,
+            ..Tsne::default()
+        };
+
+        let result = tsne.embed(&points);
+        assert_eq!(result.len(), points.len());
+        assert_eq!(result[0].len(), 2);
+        assert_eq!(tsne.embed(&points), result);
+
+        // every point is closer to the points of its cluster than to the others
+        let distance = |a: &[f64], b: &[f64]| (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2);
+        for (i, a) in result.iter().enumerate() {
+            let (mut same, mut other) = (0.0_f64, f64::INFINITY);
+            for (j, b) in result.iter().enumerate().filter(|(j, _)| *j != i) {
+                if i % 3 == j % 3 {
+                    same = same.max(distance(a, b));
+                } else {
+                    other = other.min(distance(a, b));
+                }
+            }
+            assert!(same < other);
+        }
+    }
+}
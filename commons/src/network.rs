@@ -2,6 +2,136 @@ use crate::math::lerp::lerp;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Activation {
+    // original behaviour: 1.0 when the level is above the bias, not differentiable
+    #[default]
+    Step,
+    Sigmoid,
+    Tanh,
+    Relu,
+    // slope for the negative levels
+    LeakyRelu(f64),
+    // normalizes the whole level into probabilities, meant for the output level
+    Softmax,
+}
+
+impl Activation {
+    fn apply(&self, levels: &mut [f64]) {
+        match self {
+            Activation::Step => levels
+                .iter_mut()
+                .for_each(|x| *x = if *x > 0.0 { 1.0 } else { 0.0 }),
+            Activation::Sigmoid => levels
+                .iter_mut()
+                .for_each(|x| *x = 1.0 / (1.0 + (-*x).exp())),
+            Activation::Tanh => levels.iter_mut().for_each(|x| *x = x.tanh()),
+            Activation::Relu => levels.iter_mut().for_each(|x| *x = x.max(0.0)),
+            Activation::LeakyRelu(slope) => levels
+                .iter_mut()
+                .for_each(|x| *x = if *x > 0.0 { *x } else { *x * slope }),
+            Activation::Softmax => {
+                // shifting by the max level keeps exp from overflowing
+                let max = levels.iter().fold(f64::NEG_INFINITY, |acc, x| acc.max(*x));
+                levels.iter_mut().for_each(|x| *x = (*x - max).exp());
+                let sum = levels.iter().sum::<f64>();
+                levels.iter_mut().for_each(|x| *x /= sum);
+            }
+        }
+    }
+
+    // converts the loss gradient by the outputs into the gradient by the levels,
+    // derivatives are expressed through the already activated outputs
+    fn backward(&self, outputs: &[f64], gradient: &[f64]) -> Vec<f64> {
+        let derivative = |output: f64| match self {
+            Activation::Step => 0.0,
+            Activation::Sigmoid => output * (1.0 - output),
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => {
+                if output > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::LeakyRelu(slope) => {
+                if output > 0.0 {
+                    1.0
+                } else {
+                    *slope
+                }
+            }
+            Activation::Softmax => unreachable!(),
+        };
+
+        match self {
+            Activation::Softmax => {
+                let dot = outputs
+                    .iter()
+                    .zip(gradient)
+                    .fold(0.0, |acc, (output, gradient)| acc + output * gradient);
+
+                outputs
+                    .iter()
+                    .zip(gradient)
+                    .map(|(output, gradient)| output * (gradient - dot))
+                    .collect()
+            }
+            _ => outputs
+                .iter()
+                .zip(gradient)
+                .map(|(output, gradient)| gradient * derivative(*output))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loss {
+    MeanSquaredError,
+    // expects probabilities as outputs (Softmax) and one-hot targets
+    CrossEntropy,
+}
+
+// keeps ln away from zero probabilities
+const EPSILON: f64 = 1e-12;
+
+impl Loss {
+    pub fn loss(&self, outputs: &[f64], targets: &[f64]) -> f64 {
+        match self {
+            Loss::MeanSquaredError => {
+                outputs
+                    .iter()
+                    .zip(targets)
+                    .fold(0.0, |acc, (output, target)| acc + (output - target).powi(2))
+                    / outputs.len() as f64
+            }
+            Loss::CrossEntropy => outputs
+                .iter()
+                .zip(targets)
+                .fold(0.0, |acc, (output, target)| {
+                    acc - target * output.max(EPSILON).ln()
+                }),
+        }
+    }
+
+    // derivative of the loss by every output
+    fn gradient(&self, outputs: &[f64], targets: &[f64]) -> Vec<f64> {
+        match self {
+            Loss::MeanSquaredError => outputs
+                .iter()
+                .zip(targets)
+                .map(|(output, target)| 2.0 * (output - target) / outputs.len() as f64)
+                .collect(),
+            Loss::CrossEntropy => outputs
+                .iter()
+                .zip(targets)
+                .map(|(output, target)| -target / output.max(EPSILON))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone)]
 pub struct NeuralNetwork {
     pub levels: Vec<Level>,
@@ -9,22 +139,126 @@ pub struct NeuralNetwork {
 
 impl NeuralNetwork {
     pub fn create(neuron_counts: &[usize]) -> Self {
+        Self::create_with_activation(neuron_counts, Activation::Step)
+    }
+
+    pub fn create_with_activation(neuron_counts: &[usize], activation: Activation) -> Self {
+        Self::create_with_activations(neuron_counts, activation, activation)
+    }
+
+    // hidden activation for every level but the last one, e.g. Relu + Softmax for classifiers
+    pub fn create_with_activations(
+        neuron_counts: &[usize],
+        hidden: Activation,
+        output: Activation,
+    ) -> Self {
+        let levels_count = neuron_counts.len() - 1;
         let levels = neuron_counts
             .iter()
-            .take(neuron_counts.len() - 1)
+            .take(levels_count)
             .zip(neuron_counts.iter().skip(1))
-            .map(|(count, next_count)| Level::create(*count, *next_count))
+            .zip(1..)
+            .map(|((count, next_count), index)| {
+                let activation = if index == levels_count {
+                    output
+                } else {
+                    hidden
+                };
+                Level::create(*count, *next_count, activation)
+            })
             .collect();
 
         Self { levels }
     }
 
+    // new random weights and biases, a seeded generator makes them reproducible
+    pub fn randomize(&mut self, rng: &mut impl Rng) {
+        self.levels
+            .iter_mut()
+            .for_each(|level| level.randomize(rng));
+    }
+
     pub fn feed_forward(&mut self, given_inputs: Vec<f64>) -> Vec<f64> {
         self.levels
             .iter_mut()
             .fold(given_inputs, |acc, el| el.feed_forward(acc).to_vec())
     }
 
+    // same as feed_forward, but keeps the levels untouched
+    pub fn predict(&self, given_inputs: &[f64]) -> Vec<f64> {
+        self.levels
+            .iter()
+            .fold(given_inputs.to_vec(), |acc, el| el.compute(&acc))
+    }
+
+    // propagates the loss gradient of the last feed_forward call back through the levels,
+    // the result is stored in every level, see Level::apply_gradients
+    pub fn backward(&mut self, targets: &[f64], loss: Loss) {
+        let outputs = match self.levels.last() {
+            Some(level) => &level.outputs,
+            None => return,
+        };
+
+        let gradient = loss.gradient(outputs, targets);
+
+        self.levels
+            .iter_mut()
+            .rev()
+            .fold(gradient, |acc, level| level.backward(&acc));
+    }
+
+    pub fn apply_gradients(&mut self, learning_rate: f64) {
+        self.levels
+            .iter_mut()
+            .for_each(|level| level.apply_gradients(learning_rate))
+    }
+
+    // stochastic gradient descent, returns the mean loss of every epoch
+    pub fn train(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        learning_rate: f64,
+        epochs: usize,
+    ) -> Vec<f64> {
+        self.train_with_loss(
+            inputs,
+            targets,
+            learning_rate,
+            epochs,
+            Loss::MeanSquaredError,
+        )
+    }
+
+    pub fn train_with_loss(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        learning_rate: f64,
+        epochs: usize,
+        loss: Loss,
+    ) -> Vec<f64> {
+        if inputs.len() != targets.len() {
+            panic!("inputs and targets have different sizes")
+        }
+
+        (0..epochs)
+            .map(|_| {
+                let total = inputs
+                    .iter()
+                    .zip(targets)
+                    .fold(0.0, |acc, (input, target)| {
+                        let outputs = self.feed_forward(input.clone());
+                        self.backward(target, loss);
+                        self.apply_gradients(learning_rate);
+                        acc + loss.loss(&outputs, target)
+                    });
+
+                total / inputs.len().max(1) as f64
+            })
+            .collect()
+    }
+
     // amount from 0 to 1 (aka percent)
     pub fn mutate(&mut self, amount: f64) {
         self.levels
@@ -41,10 +275,17 @@ pub struct Level {
     pub outputs: Vec<f64>,
     pub biases: Vec<f64>,
     pub weights: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub activation: Activation,
+    // filled by backward, same shapes as biases and weights
+    #[serde(skip)]
+    pub bias_gradients: Vec<f64>,
+    #[serde(skip)]
+    pub weight_gradients: Vec<Vec<f64>>,
 }
 
 impl Level {
-    fn create(inputs_count: usize, outputs_count: usize) -> Self {
+    fn create(inputs_count: usize, outputs_count: usize, activation: Activation) -> Self {
         let inputs = vec![0.0; inputs_count];
         let outputs = vec![0.0; outputs_count];
         let biases = vec![0.0; outputs_count];
@@ -55,31 +296,85 @@ impl Level {
             outputs,
             biases,
             weights,
+            activation,
+            bias_gradients: vec![],
+            weight_gradients: vec![],
         };
 
-        result.randomize();
+        result.randomize(&mut rand::thread_rng());
 
         result
     }
 
     fn feed_forward(&mut self, given_inputs: Vec<f64>) -> &[f64] {
+        self.outputs = self.compute(&given_inputs);
         self.inputs = given_inputs;
 
-        self.outputs.iter_mut().zip(0..).for_each(|(value, index)| {
-            let level = self
-                .inputs
-                .iter()
-                .zip(&self.weights)
-                .fold(0.0, |acc, (input, weights)| acc + input * weights[index]);
+        &self.outputs
+    }
 
-            *value = if level > self.biases[index] { 1.0 } else { 0.0 };
-        });
+    fn compute(&self, inputs: &[f64]) -> Vec<f64> {
+        let mut outputs = self
+            .biases
+            .iter()
+            .zip(0..)
+            .map(|(bias, index)| {
+                let level = inputs
+                    .iter()
+                    .zip(&self.weights)
+                    .fold(0.0, |acc, (input, weights)| acc + input * weights[index]);
 
-        &self.outputs
+                level - bias
+            })
+            .collect::<Vec<_>>();
+
+        self.activation.apply(&mut outputs);
+
+        outputs
     }
 
-    fn randomize(&mut self) {
-        let mut rng = rand::thread_rng();
+    // takes the loss gradient by the outputs and returns the loss gradient by the inputs
+    fn backward(&mut self, output_gradient: &[f64]) -> Vec<f64> {
+        let deltas = self.activation.backward(&self.outputs, output_gradient);
+
+        // the bias is subtracted from the level
+        self.bias_gradients = deltas.iter().map(|delta| -delta).collect();
+
+        self.weight_gradients = self
+            .inputs
+            .iter()
+            .map(|input| deltas.iter().map(|delta| input * delta).collect())
+            .collect();
+
+        self.weights
+            .iter()
+            .map(|weights| {
+                weights
+                    .iter()
+                    .zip(&deltas)
+                    .fold(0.0, |acc, (weight, delta)| acc + weight * delta)
+            })
+            .collect()
+    }
+
+    fn apply_gradients(&mut self, learning_rate: f64) {
+        self.biases
+            .iter_mut()
+            .zip(&self.bias_gradients)
+            .for_each(|(bias, gradient)| *bias -= learning_rate * gradient);
+
+        self.weights
+            .iter_mut()
+            .zip(&self.weight_gradients)
+            .for_each(|(weights, gradients)| {
+                weights
+                    .iter_mut()
+                    .zip(gradients)
+                    .for_each(|(weight, gradient)| *weight -= learning_rate * gradient)
+            });
+    }
+
+    fn randomize(&mut self, rng: &mut impl Rng) {
         self.weights.iter_mut().for_each(|arr| {
             arr.iter_mut().for_each(|el| {
                 *el = rng.gen_range(-1.0..=1.0);
@@ -104,3 +399,88 @@ impl Level {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::network::{Activation, Loss, NeuralNetwork};
+
+    #[test]
+    fn test_step_network_keeps_binary_outputs() {
+        let mut network = NeuralNetwork::create(&[3, 4, 2]);
+        let outputs = network.feed_forward(vec![0.5, 0.1, 0.9]);
+
+        assert!(outputs.iter().all(|x| *x == 0.0 || *x == 1.0));
+    }
+
+    #[test]
+    fn test_train_or() {
+        let inputs = vec![
+            vec![0.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+        ];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![1.0]];
+
+        let mut network = NeuralNetwork::create_with_activation(&[2, 1], Activation::Sigmoid);
+        let losses = network.train(&inputs, &targets, 1.0, 2000);
+
+        assert!(losses.last().expect("") < &losses[0]);
+
+        for (input, target) in inputs.iter().zip(&targets) {
+            let output = network.feed_forward(input.clone());
+            assert!(Loss::MeanSquaredError.loss(&output, target) < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_legacy_json_is_step() {
+        let json =
+            r#"{"levels":[{"inputs":[0.0],"outputs":[0.0],"biases":[0.5],"weights":[[1.0]]}]}"#;
+        let mut network = serde_json::from_str::<NeuralNetwork>(json).expect("");
+
+        assert_eq!(network.levels[0].activation, Activation::Step);
+        assert_eq!(network.feed_forward(vec![1.0]), vec![1.0]);
+        assert_eq!(network.feed_forward(vec![0.2]), vec![0.0]);
+    }
+
+    #[test]
+    fn test_activation_is_serialized() {
+        let network = NeuralNetwork::create_with_activations(
+            &[2, 3, 2],
+            Activation::LeakyRelu(0.1),
+            Activation::Softmax,
+        );
+        let json = serde_json::to_string(&network).expect("");
+        let network = serde_json::from_str::<NeuralNetwork>(&json).expect("");
+
+        assert_eq!(network.levels[0].activation, Activation::LeakyRelu(0.1));
+        assert_eq!(network.levels[1].activation, Activation::Softmax);
+    }
+
+    #[test]
+    fn test_train_softmax_classifier() {
+        let inputs = vec![
+            vec![0.0, 0.1],
+            vec![0.1, 0.0],
+            vec![0.9, 1.0],
+            vec![1.0, 0.9],
+        ];
+        let targets = vec![
+            vec![1.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.0, 1.0],
+        ];
+
+        let mut network =
+            NeuralNetwork::create_with_activations(&[2, 2], Activation::Tanh, Activation::Softmax);
+        network.train_with_loss(&inputs, &targets, 0.5, 1000, Loss::CrossEntropy);
+
+        for (input, target) in inputs.iter().zip(&targets) {
+            let output = network.feed_forward(input.clone());
+            assert!((output.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!(Loss::CrossEntropy.loss(&output, target) < 0.3);
+        }
+    }
+}